@@ -0,0 +1,340 @@
+//! Loot table parsing and random selection, factored out of the GUI so it can be
+//! unit-tested and reused without spinning up `iui`.
+
+use std::any::Any;
+use std::fmt;
+use std::io::BufRead;
+use std::str::FromStr;
+
+use rand::distributions::WeightedIndex;
+use rand::Rng;
+use rand_distr::{Distribution, Uniform};
+use serde::Deserialize;
+
+pub mod yaml;
+
+/// Which selection strategy a parsed loot table uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum Format {
+    Weighted,
+    Uniform,
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Format::Weighted => write!(f, "Weighted"),
+            Format::Uniform => write!(f, "Uniform"),
+        }
+    }
+}
+
+/// Everything that can go wrong while parsing a loot table file.
+#[derive(Debug)]
+pub enum LootError {
+    /// The file was empty, or only contained comments, before a header was found.
+    MissingHeader,
+    /// The first non-commented line wasn't `Weighted` or `Uniform`.
+    UnknownHeader(String),
+    /// A `Weighted` line's weight field didn't parse as a `u32`.
+    BadWeight { line: usize, value: String },
+    /// A `Weighted` line wasn't split into exactly name and weight by `!!`.
+    WrongSeparator { line: usize },
+    /// The file couldn't be opened or read.
+    Io(String),
+    /// A `.yml`/`.yaml` loot table failed to deserialize.
+    Yaml(String),
+    /// A YAML table (or sub-table) had no `entries` to pick from.
+    EmptyTable,
+    /// A YAML leaf's `count` had `min` greater than `max`.
+    InvalidCountRange { min: u32, max: u32 },
+    /// A `Weighted` YAML table (or sub-table) had every entry at `weight: 0`.
+    AllWeightsZero,
+}
+
+impl fmt::Display for LootError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LootError::MissingHeader => write!(
+                f,
+                "Error found in format. First non-commented line should be \"Weighted\" or \"Uniform\"."
+            ),
+            LootError::UnknownHeader(header) => write!(
+                f,
+                "Error found in format. First non-commented line should be \"Weighted\" or \"Uniform\", found \"{}\".",
+                header
+            ),
+            LootError::BadWeight { line, value } => write!(
+                f,
+                "Error found when parsing weight \"{}\" on line {}.",
+                value, line
+            ),
+            LootError::WrongSeparator { line } => write!(
+                f,
+                "Error found when parsing line {}. Name and weight should be separated by \"!!\".\n\tEx: greatsword!!10",
+                line
+            ),
+            LootError::Io(message) => write!(f, "Error reading loot table file: {}", message),
+            LootError::Yaml(message) => write!(f, "Error parsing YAML loot table: {}", message),
+            LootError::EmptyTable => write!(f, "Error in YAML loot table: a table has no entries to pick from."),
+            LootError::InvalidCountRange { min, max } => write!(
+                f,
+                "Error in YAML loot table: count range min ({}) is greater than max ({}).",
+                min, max
+            ),
+            LootError::AllWeightsZero => write!(
+                f,
+                "Error in YAML loot table: a Weighted table has every entry at weight 0."
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LootError {}
+
+/// Anything that can appear in a loot table and be selected by name.
+pub trait Loot {
+    fn print(&self);
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl fmt::Debug for dyn Loot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(item) = self.as_any().downcast_ref::<WeightedLoot>() {
+            write!(f, "{} (weight {})", item.name, item.weight)
+        } else if let Some(name) = self.as_any().downcast_ref::<String>() {
+            write!(f, "{}", name)
+        } else {
+            write!(f, "<unknown loot item>")
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct WeightedLoot {
+    pub name: String,
+    pub weight: u32,
+}
+
+impl Loot for WeightedLoot {
+    fn print(&self) {
+        println!("{:?}", self);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Loot for String {
+    fn print(&self) {
+        println!("{:?}", self);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Parses a loot table from `reader`, returning its [`Format`] and the entries it
+/// describes. Comment lines starting with `#` are skipped, and the first
+/// non-commented line selects the format via the `Weighted`/`Uniform` header.
+pub fn parse_loot_table<R: BufRead>(reader: R) -> Result<(Format, Vec<Box<dyn Loot>>), LootError> {
+    let mut loot_table: Vec<Box<dyn Loot>> = Vec::new();
+    let mut format: Option<Format> = None;
+
+    for (line_no, line) in reader.lines().enumerate() {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => continue, // continue to try the next line rather than abort
+        };
+
+        if line.starts_with('#') {
+            continue; // this line is a comment, so we ignore
+        }
+
+        // If format is not assigned, check first line for format (Weighted or Uniform)
+        let format = match format {
+            Some(format) => format,
+            None => {
+                let parsed = match line.as_str() {
+                    "Weighted" => Format::Weighted,
+                    "Uniform" => Format::Uniform,
+                    _ => return Err(LootError::UnknownHeader(line)),
+                };
+                format = Some(parsed);
+                continue;
+            }
+        };
+
+        match format {
+            // If Weighted, split line by !! and populate loot_table accordingly
+            Format::Weighted => {
+                let tokens: Vec<&str> = line.split("!!").collect();
+                if tokens.len() != 2 {
+                    return Err(LootError::WrongSeparator { line: line_no + 1 });
+                }
+                let weight = FromStr::from_str(tokens[1]).map_err(|_| LootError::BadWeight {
+                    line: line_no + 1,
+                    value: tokens[1].to_string(),
+                })?;
+                // push a Boxed WeightedLoot struct into the vector
+                loot_table.push(Box::new(WeightedLoot {
+                    name: String::from(tokens[0]),
+                    weight,
+                }));
+            }
+            // If Uniform
+            // TODO: Check if line contains \"!!\" and warn user that they may be using the wrong format
+            Format::Uniform => {
+                loot_table.push(Box::new(line));
+            }
+        }
+    }
+
+    match format {
+        Some(format) => Ok((format, loot_table)),
+        None => Err(LootError::MissingHeader),
+    }
+}
+
+/// Uses a Uniform distribution to randomly choose an item.
+/// Returns the name of the chosen item as a String.
+pub fn pick_random_uniform(items: &[Box<dyn Loot>], rng: &mut impl Rng) -> String {
+    let result = &items[Uniform::from(0..items.len()).sample(rng)];
+    match result.as_any().downcast_ref::<String>() {
+        Some(val) => String::from(val),
+        None => panic!("Unable to get String from item Box."),
+    }
+}
+
+/// Populates a weighted table and chooses randomly.
+/// Returns the name of the chosen item as a String.
+pub fn pick_random_weighted(items: &[Box<dyn Loot>], rng: &mut impl Rng) -> String {
+    let mut choices = Vec::new();
+    let mut weights = Vec::new();
+
+    for item in items {
+        let item = match item.as_any().downcast_ref::<WeightedLoot>() {
+            Some(item) => item,
+            None => panic!("Unable to get WeightedLoot from Box."),
+        };
+
+        choices.push(String::from(&item.name));
+        weights.push(item.weight);
+    }
+
+    let dist = WeightedIndex::new(&weights).unwrap();
+    String::from(&choices[dist.sample(rng)])
+}
+
+/// A loaded loot table, in whichever format it was parsed from.
+pub enum LootSource {
+    /// The flat, line-based `!!`-delimited format.
+    Flat(Format, Vec<Box<dyn Loot>>),
+    /// The recursive YAML format, see [`yaml`].
+    Yaml(yaml::YamlTable),
+}
+
+impl LootSource {
+    /// Picks loot, returning one name for a flat table, or however many names the
+    /// rolled YAML leaf's `count` range produced.
+    pub fn pick(&self, rng: &mut impl Rng) -> Vec<String> {
+        match self {
+            LootSource::Flat(Format::Weighted, items) => vec![pick_random_weighted(items, rng)],
+            LootSource::Flat(Format::Uniform, items) => vec![pick_random_uniform(items, rng)],
+            LootSource::Yaml(table) => yaml::pick_yaml(table, rng),
+        }
+    }
+}
+
+/// Loads a loot table from `path`, using the YAML format (see [`yaml`]) when the
+/// extension is `.yml`/`.yaml` or the file's first line is `Format: yaml`, and the
+/// flat `!!`-delimited format otherwise.
+pub fn load_loot_table(path: &str) -> Result<LootSource, LootError> {
+    let content = std::fs::read_to_string(path).map_err(|err| LootError::Io(err.to_string()))?;
+
+    let is_yaml_ext = path.ends_with(".yml") || path.ends_with(".yaml");
+    let first_line = content.lines().next().unwrap_or("").trim();
+    let has_yaml_header = first_line == "Format: yaml";
+
+    if is_yaml_ext || has_yaml_header {
+        let body = if has_yaml_header {
+            content.split_once('\n').map_or("", |(_, rest)| rest)
+        } else {
+            content.as_str()
+        };
+        let table = yaml::parse_yaml_table(body.as_bytes())?;
+        Ok(LootSource::Yaml(table))
+    } else {
+        let (format, loot_table) = parse_loot_table(std::io::Cursor::new(content.as_bytes()))?;
+        Ok(LootSource::Flat(format, loot_table))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn parses_weighted_table() {
+        let input = "Weighted\nsword!!10\nshield!!5\n";
+        let (format, items) = parse_loot_table(input.as_bytes()).unwrap();
+        assert_eq!(format, Format::Weighted);
+        assert_eq!(items.len(), 2);
+    }
+
+    #[test]
+    fn parses_uniform_table() {
+        let input = "Uniform\nsword\nshield\n";
+        let (format, items) = parse_loot_table(input.as_bytes()).unwrap();
+        assert_eq!(format, Format::Uniform);
+        assert_eq!(items.len(), 2);
+    }
+
+    #[test]
+    fn skips_comment_lines() {
+        let input = "# a comment\nWeighted\n# another comment\nsword!!10\n";
+        let (_, items) = parse_loot_table(input.as_bytes()).unwrap();
+        assert_eq!(items.len(), 1);
+    }
+
+    #[test]
+    fn missing_header_is_an_error() {
+        let input = "# just a comment\n";
+        assert!(matches!(parse_loot_table(input.as_bytes()), Err(LootError::MissingHeader)));
+    }
+
+    #[test]
+    fn unknown_header_is_an_error() {
+        let input = "Bogus\nsword!!10\n";
+        assert!(matches!(parse_loot_table(input.as_bytes()), Err(LootError::UnknownHeader(_))));
+    }
+
+    #[test]
+    fn bad_weight_is_an_error() {
+        let input = "Weighted\nsword!!not-a-number\n";
+        assert!(matches!(parse_loot_table(input.as_bytes()), Err(LootError::BadWeight { .. })));
+    }
+
+    #[test]
+    fn wrong_separator_is_an_error() {
+        let input = "Weighted\nsword:10\n";
+        assert!(matches!(parse_loot_table(input.as_bytes()), Err(LootError::WrongSeparator { .. })));
+    }
+
+    #[test]
+    fn same_seed_picks_the_same_sequence() {
+        let input = "Weighted\nsword!!10\nshield!!5\nbow!!1\n";
+        let (format, items) = parse_loot_table(input.as_bytes()).unwrap();
+        let source = LootSource::Flat(format, items);
+
+        let mut first = StdRng::seed_from_u64(42);
+        let mut second = StdRng::seed_from_u64(42);
+        let picks_a: Vec<String> = (0..20).map(|_| source.pick(&mut first).join(", ")).collect();
+        let picks_b: Vec<String> = (0..20).map(|_| source.pick(&mut second).join(", ")).collect();
+        assert_eq!(picks_a, picks_b);
+    }
+}
@@ -0,0 +1,221 @@
+//! The richer, YAML-based loot table format: a recursive tree of sub-tables and
+//! leaf entries, as opposed to the flat `!!`-delimited text format in `lib.rs`.
+
+use std::io::Read;
+
+use rand::distributions::WeightedIndex;
+use rand::Rng;
+use rand_distr::{Distribution, Uniform};
+use serde::Deserialize;
+
+use crate::{Format, LootError};
+
+/// An inclusive quantity range a leaf entry is rolled for, e.g. `count: {min: 1, max: 3}`.
+#[derive(Debug, Deserialize)]
+pub struct CountRange {
+    pub min: u32,
+    pub max: u32,
+}
+
+/// A single item at the bottom of a loot tree.
+#[derive(Debug, Deserialize)]
+pub struct YamlLeaf {
+    pub name: String,
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+    #[serde(default)]
+    pub count: Option<CountRange>,
+}
+
+/// A nested loot table: its own `Weighted`/`Uniform` mode, a weight in its parent
+/// table, and the entries (leaves or further sub-tables) it picks from.
+#[derive(Debug, Deserialize)]
+pub struct YamlTable {
+    #[serde(default = "default_format")]
+    pub format: Format,
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+    pub entries: Vec<YamlEntry>,
+}
+
+/// An entry in a [`YamlTable`]: either a leaf item or a further sub-table.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum YamlEntry {
+    Table(YamlTable),
+    Leaf(YamlLeaf),
+}
+
+fn default_weight() -> u32 {
+    1
+}
+
+fn default_format() -> Format {
+    Format::Weighted
+}
+
+/// Parses a YAML loot table from `reader`, then validates it so `pick_yaml` never
+/// has to trust a successful deserialize alone: every table (at any depth) must
+/// have at least one entry, every leaf's `count` range must have `min <= max`, and
+/// a `Weighted` table's entries can't all be at `weight: 0` (which `WeightedIndex`
+/// itself refuses to sample from).
+pub fn parse_yaml_table<R: Read>(reader: R) -> Result<YamlTable, LootError> {
+    let table: YamlTable = serde_yaml::from_reader(reader).map_err(|err| LootError::Yaml(err.to_string()))?;
+    validate_table(&table)?;
+    Ok(table)
+}
+
+fn validate_table(table: &YamlTable) -> Result<(), LootError> {
+    if table.entries.is_empty() {
+        return Err(LootError::EmptyTable);
+    }
+    if table.format == Format::Weighted && table.entries.iter().all(|entry| entry_weight(entry) == 0) {
+        return Err(LootError::AllWeightsZero);
+    }
+    for entry in &table.entries {
+        match entry {
+            YamlEntry::Table(sub) => validate_table(sub)?,
+            YamlEntry::Leaf(leaf) => {
+                if let Some(range) = &leaf.count {
+                    if range.min > range.max {
+                        return Err(LootError::InvalidCountRange { min: range.min, max: range.max });
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A short human-readable label for an entry, for display in a flat list (e.g. the
+/// TUI's loot list), before any recursive picking happens.
+pub fn entry_label(entry: &YamlEntry) -> String {
+    match entry {
+        YamlEntry::Table(table) => format!("<table: {} entries, weight {}>", table.entries.len(), table.weight),
+        YamlEntry::Leaf(leaf) => format!("{} (weight {})", leaf.name, leaf.weight),
+    }
+}
+
+fn entry_weight(entry: &YamlEntry) -> u32 {
+    match entry {
+        YamlEntry::Table(table) => table.weight,
+        YamlEntry::Leaf(leaf) => leaf.weight,
+    }
+}
+
+/// Recursively picks loot from `table`: selects an entry at this level (via
+/// `WeightedIndex` for `Weighted`, or a flat `Uniform` draw otherwise), recurses
+/// if that entry is a sub-table, and otherwise rolls the leaf's `count` range
+/// (default: exactly once) and returns one name per roll.
+pub fn pick_yaml(table: &YamlTable, rng: &mut impl Rng) -> Vec<String> {
+    let chosen = match table.format {
+        Format::Weighted => {
+            let weights: Vec<u32> = table.entries.iter().map(entry_weight).collect();
+            let dist = WeightedIndex::new(&weights).unwrap();
+            &table.entries[dist.sample(rng)]
+        }
+        Format::Uniform => &table.entries[Uniform::from(0..table.entries.len()).sample(rng)],
+    };
+
+    match chosen {
+        YamlEntry::Table(sub) => pick_yaml(sub, rng),
+        YamlEntry::Leaf(leaf) => {
+            let (min, max) = leaf.count.as_ref().map_or((1, 1), |c| (c.min, c.max));
+            let rolls = if min == max { min } else { Uniform::from(min..=max).sample(rng) };
+            (0..rolls).map(|_| leaf.name.clone()).collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn empty_entries_is_an_error() {
+        let yaml = "format: Weighted\nentries: []\n";
+        assert!(matches!(parse_yaml_table(yaml.as_bytes()), Err(LootError::EmptyTable)));
+    }
+
+    #[test]
+    fn empty_sub_table_is_an_error() {
+        let yaml = "
+            entries:
+              - entries: []
+        ";
+        assert!(matches!(parse_yaml_table(yaml.as_bytes()), Err(LootError::EmptyTable)));
+    }
+
+    #[test]
+    fn all_zero_weights_is_an_error() {
+        let yaml = "
+            format: Weighted
+            entries:
+              - name: gem
+                weight: 0
+              - name: sword
+                weight: 0
+        ";
+        assert!(matches!(parse_yaml_table(yaml.as_bytes()), Err(LootError::AllWeightsZero)));
+    }
+
+    #[test]
+    fn all_zero_weights_is_fine_for_uniform_tables() {
+        let yaml = "
+            format: Uniform
+            entries:
+              - name: gem
+                weight: 0
+              - name: sword
+                weight: 0
+        ";
+        assert!(parse_yaml_table(yaml.as_bytes()).is_ok());
+    }
+
+    #[test]
+    fn inverted_count_range_is_an_error() {
+        let yaml = "
+            entries:
+              - name: gem
+                count: { min: 5, max: 1 }
+        ";
+        assert!(matches!(
+            parse_yaml_table(yaml.as_bytes()),
+            Err(LootError::InvalidCountRange { min: 5, max: 1 })
+        ));
+    }
+
+    #[test]
+    fn recursively_picks_a_leaf() {
+        let yaml = "
+            entries:
+              - entries:
+                  - name: gem
+              - name: sword
+        ";
+        let table = parse_yaml_table(yaml.as_bytes()).unwrap();
+        let mut rng = StdRng::seed_from_u64(7);
+        let picks = pick_yaml(&table, &mut rng);
+        assert!(picks.iter().all(|name| name == "gem" || name == "sword"));
+    }
+
+    #[test]
+    fn same_seed_picks_the_same_sequence() {
+        let yaml = "
+            entries:
+              - name: gem
+                weight: 3
+              - name: sword
+                weight: 1
+        ";
+        let table = parse_yaml_table(yaml.as_bytes()).unwrap();
+
+        let mut first = StdRng::seed_from_u64(99);
+        let mut second = StdRng::seed_from_u64(99);
+        let picks_a: Vec<Vec<String>> = (0..20).map(|_| pick_yaml(&table, &mut first)).collect();
+        let picks_b: Vec<Vec<String>> = (0..20).map(|_| pick_yaml(&table, &mut second)).collect();
+        assert_eq!(picks_a, picks_b);
+    }
+}
@@ -0,0 +1,301 @@
+//! The `iui` window frontend: the default way to run the loot picker.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+extern crate iui;
+use iui::prelude::*;
+use iui::controls::{Label, Spinbox, Slider, Entry, MultilineEntry, LayoutGrid,
+    GridAlignment, GridExpand, HorizontalSeparator, Button};
+
+use loot::{Format, Loot, LootSource, WeightedLoot};
+
+use crate::watch;
+use crate::AppRng;
+
+/// This struct will hold the values that multiple callbacks will need to access.
+struct State {
+    slider_val: i64,
+    spinner_val: i64,
+    entry_val: String,
+    multi_val: String,
+    loot_val: String,
+    source: LootSource,
+    rng: AppRng,
+    status: String,
+    roll_counts: HashMap<String, u32>,
+}
+
+/// For `Weighted` flat tables, the configured weight of each named item, so
+/// observed roll counts can be compared against the intended odds.
+fn configured_weights(source: &LootSource) -> Option<HashMap<String, u32>> {
+    match source {
+        LootSource::Flat(Format::Weighted, items) => Some(
+            items
+                .iter()
+                .filter_map(|item| item.as_any().downcast_ref::<WeightedLoot>())
+                .map(|item| (item.name.clone(), item.weight))
+                .collect(),
+        ),
+        _ => None,
+    }
+}
+
+/// Renders the roll-count table: each item's roll count and observed percentage,
+/// plus the expected percentage from its configured weight and the delta when
+/// available. The configured weight itself is not printed.
+fn format_roll_stats(roll_counts: &HashMap<String, u32>, weights: Option<&HashMap<String, u32>>) -> String {
+    if roll_counts.is_empty() {
+        return "No rolls yet.".into();
+    }
+
+    let total_rolls: u32 = roll_counts.values().sum();
+    let total_weight: u32 = weights.map_or(0, |w| w.values().sum());
+
+    let mut names: Vec<&String> = roll_counts.keys().collect();
+    names.sort();
+
+    let mut lines = vec![format!("Rolls: {}", total_rolls)];
+    for name in names {
+        let count = roll_counts[name];
+        let observed_pct = 100.0 * count as f64 / total_rolls as f64;
+        match weights.and_then(|w| w.get(name)) {
+            Some(&weight) if total_weight > 0 => {
+                let expected_pct = 100.0 * weight as f64 / total_weight as f64;
+                lines.push(format!(
+                    "{}: {} rolls, {:.1}% observed vs {:.1}% expected (Δ{:+.1})",
+                    name, count, observed_pct, expected_pct, observed_pct - expected_pct
+                ));
+            }
+            _ => lines.push(format!("{}: {} rolls, {:.1}% observed", name, count, observed_pct)),
+        }
+    }
+    lines.join("\n")
+}
+
+/// Opens the `iui` window over `source`, and watches `path` on disk so the table
+/// can be edited and reloaded without relaunching.
+pub(crate) fn run(source: LootSource, rng: AppRng, path: String) {
+    // Initialize the UI framework.
+    let ui = UI::init().unwrap();
+
+    // Initialize the state of the application.
+    let state = Rc::new(RefCell::new(State {
+        slider_val: 0, spinner_val: 0, entry_val: "".into(), multi_val: "".into(), loot_val: "".into(),
+        source, rng, status: "".into(), roll_counts: HashMap::new(),
+    }));
+
+    // Watch the loot table file so edits on disk show up without a restart.
+    let reloads = watch::watch(path);
+
+    // Create the grid which we'll use to lay out controls
+    let mut grid = LayoutGrid::new(&ui);
+    grid.set_padded(&ui, true);
+
+    // Set up the inputs for the application.
+    // While it's not necessary to create a block for this, it makes the code a lot easier
+    // to read; the indentation presents a visual cue informing the reader that these
+    // statements are related.
+    let (mut slider, mut spinner, mut entry, mut multi, mut button, mut roll_n_button) = {
+        // Numerical inputs
+        let slider = Slider::new(&ui, 1, 100);
+        let spinner = Spinbox::new(&ui, 1, 100);
+        // Text inputs
+        let entry = Entry::new(&ui);
+        let multi = MultilineEntry::new(&ui);
+        let button = Button::new(&ui, "Pick Loot");
+        // Rolls the spinner's value worth of times in one pass, to build up the
+        // roll-count table below faster than clicking "Pick Loot" repeatedly.
+        let roll_n_button = Button::new(&ui, "Roll N (uses the spinner above)");
+        // Add everything into the grid
+        grid.append(&ui, slider.clone(),
+            // This is position (by slot) and size, expansion, and alignment.
+            // In this case, row 0, col 0, 1 by 1, compress as much as possible,
+            // and align to the fill.
+            0, 0, 1, 1, GridExpand::Neither, GridAlignment::Fill, GridAlignment::Fill);
+        grid.append(&ui, spinner.clone(),
+            // This one is at column zero, row 1.
+            0, 1, 1, 1, GridExpand::Neither, GridAlignment::Fill, GridAlignment::Fill);
+        grid.append(&ui, HorizontalSeparator::new(&ui),
+            0, 3, 1, 1, GridExpand::Neither, GridAlignment::Fill, GridAlignment::Fill);
+        grid.append(&ui, entry.clone(),
+            0, 4, 1, 1, GridExpand::Neither, GridAlignment::Fill, GridAlignment::Fill);
+        grid.append(&ui, multi.clone(),
+            // The multiline entry is at column 0, row 1, and expands vertically.
+            0, 5, 1, 1, GridExpand::Vertical, GridAlignment::Fill, GridAlignment::Fill);
+        grid.append(&ui, button.clone(),
+            0, 6, 1, 1, GridExpand::Vertical, GridAlignment::Fill, GridAlignment::Fill);
+        grid.append(&ui, roll_n_button.clone(),
+            0, 7, 1, 1, GridExpand::Vertical, GridAlignment::Fill, GridAlignment::Fill);
+        (slider, spinner, entry, multi, button, roll_n_button)
+    };
+
+    // Set up the outputs for the application. Organization is very similar to the
+    // previous setup.
+    let (add_label, sub_label, text_label, bigtext_label, random_item_label, status_label, stats_label) = {
+        let add_label = Label::new(&ui, "");
+        let sub_label = Label::new(&ui, "");
+        let text_label = Label::new(&ui, "");
+        let bigtext_label = Label::new(&ui, "");
+        let random_item_label = Label::new(&ui, "");
+        let status_label = Label::new(&ui, "");
+        let stats_label = Label::new(&ui, "No rolls yet.");
+        grid.append(&ui, add_label.clone(),
+            1, 0, 1, 1, GridExpand::Neither, GridAlignment::Fill, GridAlignment::Fill);
+        grid.append(&ui, sub_label.clone(),
+            1, 1, 1, 1, GridExpand::Neither, GridAlignment::Fill, GridAlignment::Fill);
+        // We skip the #2 & 3 slots so that the text labels will align with their inputs.
+        // This is important because the big text label can expand vertically.
+        grid.append(&ui, text_label.clone(),
+            1, 4, 1, 1, GridExpand::Neither, GridAlignment::Fill, GridAlignment::Fill);
+        grid.append(&ui, bigtext_label.clone(),
+            1, 5, 1, 1, GridExpand::Neither, GridAlignment::Fill, GridAlignment::Fill);
+        grid.append(&ui, random_item_label.clone(),
+            1, 6, 1, 1, GridExpand::Neither, GridAlignment::Fill, GridAlignment::Fill);
+        grid.append(&ui, status_label.clone(),
+            1, 7, 1, 1, GridExpand::Neither, GridAlignment::Fill, GridAlignment::Fill);
+        // Spans both columns since the roll-count table can get wider than one column.
+        grid.append(&ui, stats_label.clone(),
+            0, 8, 2, 1, GridExpand::Both, GridAlignment::Fill, GridAlignment::Fill);
+        (add_label, sub_label, text_label, bigtext_label, random_item_label, status_label, stats_label)
+    };
+
+    // The window allows all constituent components to be displayed.
+    let mut window = Window::new(&ui, "Loot Picker", 300, 150, WindowType::HasMenubar);
+    window.set_child(&ui, grid);
+    window.show(&ui);
+
+    // These on_changed functions allow updating the application state when a
+    // control changes its value.
+
+    slider.on_changed(&ui, {
+        let state = state.clone();
+        move |val| { state.borrow_mut().slider_val = val; }
+    });
+
+    spinner.on_changed(&ui, {
+        let state = state.clone();
+        move |val| { state.borrow_mut().spinner_val = val; }
+    });
+
+    entry.on_changed(&ui, {
+        let state = state.clone();
+        move |val| { state.borrow_mut().entry_val = val; }
+    });
+
+    multi.on_changed(&ui, {
+        let state = state.clone();
+        move |val| { state.borrow_mut().multi_val = val; }
+    });
+
+    button.on_clicked(&ui, {
+        let state = state.clone();
+        move |_| {
+            let mut state = state.borrow_mut();
+            let state = &mut *state;
+            let picks = state.source.pick(&mut state.rng);
+            for name in &picks {
+                *state.roll_counts.entry(name.clone()).or_insert(0) += 1;
+            }
+            state.loot_val = picks.join(", ");
+        }
+    });
+
+    roll_n_button.on_clicked(&ui, {
+        let state = state.clone();
+        move |_| {
+            let mut state = state.borrow_mut();
+            let state = &mut *state;
+            let n = state.spinner_val.max(0) as u32;
+            for _ in 0..n {
+                let picks = state.source.pick(&mut state.rng);
+                for name in picks {
+                    *state.roll_counts.entry(name).or_insert(0) += 1;
+                }
+            }
+        }
+    });
+
+    // Rather than just invoking ui.run(), using EventLoop gives a lot more control
+    // over the user interface event loop.
+    // Here, the on_tick() callback is used to update the view against the state.
+    let mut event_loop = ui.event_loop();
+    event_loop.on_tick(&ui, {
+        let ui = ui.clone();
+        let mut add_label = add_label.clone();
+        let mut sub_label = sub_label.clone();
+        let mut text_label = text_label.clone();
+        let mut bigtext_label = bigtext_label.clone();
+        let mut random_item_label = bigtext_label.clone();
+        let mut status_label = status_label.clone();
+        let mut stats_label = stats_label.clone();
+        move || {
+            // Drain any pending file-watch reloads before redrawing, so a table edit
+            // on disk is reflected on the very next tick instead of the next click.
+            while let Ok(reload) = reloads.try_recv() {
+                let mut state = state.borrow_mut();
+                match reload {
+                    watch::Reload::Ok(new_source) => {
+                        state.source = new_source;
+                        state.status = "Loot table reloaded.".into();
+                        // The new table's weights/items may not match the old ones, so
+                        // stale counts would make the observed-vs-expected stats lie.
+                        state.roll_counts.clear();
+                    }
+                    watch::Reload::Err(err) => {
+                        state.status = format!("Reload failed, keeping previous table: {}", err);
+                    }
+                }
+            }
+
+            let state = state.borrow();
+
+            // Update all the labels
+            add_label.set_text(&ui, &format!("Added: {}", state.slider_val + state.spinner_val));
+            sub_label.set_text(&ui, &format!("Subtracted: {}", state.slider_val - state.spinner_val));
+            text_label.set_text(&ui, &format!("Text: {}", state.entry_val));
+            bigtext_label.set_text(&ui, &format!("Multiline Text: {}", state.multi_val));
+            random_item_label.set_text(&ui, &format!("Selected Item: {}", state.loot_val));
+            status_label.set_text(&ui, &state.status);
+            stats_label.set_text(&ui, &format_roll_stats(&state.roll_counts, configured_weights(&state.source).as_ref()));
+        }
+    });
+    event_loop.run(&ui);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_rolls_yet() {
+        let counts = HashMap::new();
+        assert_eq!(format_roll_stats(&counts, None), "No rolls yet.");
+    }
+
+    #[test]
+    fn without_configured_weights_shows_only_observed() {
+        let mut counts = HashMap::new();
+        counts.insert("sword".to_string(), 3);
+        let stats = format_roll_stats(&counts, None);
+        assert_eq!(stats, "Rolls: 3\nsword: 3 rolls, 100.0% observed");
+    }
+
+    #[test]
+    fn with_configured_weights_shows_expected_and_delta() {
+        let mut counts = HashMap::new();
+        counts.insert("sword".to_string(), 3);
+        counts.insert("shield".to_string(), 1);
+
+        let mut weights = HashMap::new();
+        weights.insert("sword".to_string(), 1);
+        weights.insert("shield".to_string(), 1);
+
+        let stats = format_roll_stats(&counts, Some(&weights));
+        assert_eq!(
+            stats,
+            "Rolls: 4\nshield: 1 rolls, 25.0% observed vs 50.0% expected (Δ-25.0)\nsword: 3 rolls, 75.0% observed vs 50.0% expected (Δ+25.0)"
+        );
+    }
+}
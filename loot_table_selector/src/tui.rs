@@ -0,0 +1,158 @@
+//! Terminal UI frontend, used in place of the `iui` window when `--tui` is passed.
+//!
+//! This is an alternative presentation over the same `loot::LootSource` that the
+//! `iui` window uses; it doesn't touch parsing or selection at all.
+
+use std::io::{self, Stdout};
+use std::panic;
+
+use crossterm::event::{self, Event, KeyCode};
+use crossterm::execute;
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use tui::backend::CrosstermBackend;
+use tui::layout::{Constraint, Direction, Layout};
+use tui::style::{Color, Style};
+use tui::widgets::{Block, Borders, List, ListItem, ListState};
+use tui::Terminal;
+
+use loot::yaml::entry_label;
+use loot::{Format, Loot, LootSource, WeightedLoot};
+
+use crate::AppRng;
+
+/// Leaves the alternate screen and disables raw mode before handing off to the
+/// default panic handler, so a parse `panic!` doesn't leave the terminal corrupted.
+fn install_panic_hook() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let _ = disable_raw_mode();
+        let _ = execute!(io::stdout(), LeaveAlternateScreen);
+        default_hook(info);
+    }));
+}
+
+fn setup_terminal() -> io::Result<Terminal<CrosstermBackend<Stdout>>> {
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen)?;
+    Terminal::new(CrosstermBackend::new(stdout))
+}
+
+fn restore_terminal(terminal: &mut Terminal<CrosstermBackend<Stdout>>) -> io::Result<()> {
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()
+}
+
+/// How many `█` characters the heaviest bar gets; lighter bars are scaled
+/// proportionally, so bars stay comparable regardless of terminal width.
+const MAX_BAR_WIDTH: usize = 30;
+
+/// Renders `bars` as horizontal bars: each line is the item's name, a bar whose
+/// length is proportional to its weight, and the weight itself. `tui` 0.19's
+/// `BarChart` widget only draws vertical bars, so a `List` of pre-drawn bar
+/// strings is used to get a horizontal layout instead.
+fn horizontal_bar_items<'a>(bars: &'a [(String, u64)]) -> Vec<ListItem<'a>> {
+    let max_weight = bars.iter().map(|(_, w)| *w).max().unwrap_or(0);
+    bars.iter()
+        .map(|(name, weight)| {
+            let bar_len = if max_weight == 0 {
+                0
+            } else {
+                (*weight as usize * MAX_BAR_WIDTH / max_weight as usize).max(1)
+            };
+            let bar = "█".repeat(bar_len);
+            ListItem::new(format!("{name} {bar} ({weight})"))
+        })
+        .collect()
+}
+
+/// Runs the loot picker as a terminal UI instead of opening an `iui` window.
+///
+/// Shows the loot table as a selectable list on the left, a scrolling roll
+/// history on the right, and (for flat `Weighted` tables) horizontal bars of
+/// the configured weights underneath. Press `r` to roll, `q` or `Esc` to quit.
+pub(crate) fn run(source: LootSource, mut rng: AppRng) -> io::Result<()> {
+    install_panic_hook();
+    let mut terminal = setup_terminal()?;
+
+    let names: Vec<String> = match &source {
+        LootSource::Flat(_, loot_table) => loot_table.iter().map(|item| format!("{:?}", item)).collect(),
+        LootSource::Yaml(table) => table.entries.iter().map(entry_label).collect(),
+    };
+    let mut list_state = ListState::default();
+    if !names.is_empty() {
+        list_state.select(Some(0));
+    }
+
+    let bars: Vec<(String, u64)> = match &source {
+        LootSource::Flat(Format::Weighted, loot_table) => loot_table
+            .iter()
+            .filter_map(|item| item.as_any().downcast_ref::<WeightedLoot>())
+            .map(|w| (w.name.clone(), w.weight as u64))
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    let mut history: Vec<String> = Vec::new();
+
+    loop {
+        terminal.draw(|frame| {
+            let size = frame.size();
+            let columns = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .split(size);
+
+            let left = if bars.is_empty() {
+                vec![columns[0]]
+            } else {
+                Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+                    .split(columns[0])
+            };
+
+            let items: Vec<ListItem> = names.iter().map(|name| ListItem::new(name.as_str())).collect();
+            let list = List::new(items)
+                .block(Block::default().borders(Borders::ALL).title("Loot Table"))
+                .highlight_style(Style::default().fg(Color::Yellow));
+            frame.render_stateful_widget(list, left[0], &mut list_state);
+
+            if !bars.is_empty() {
+                let chart = List::new(horizontal_bar_items(&bars))
+                    .block(Block::default().borders(Borders::ALL).title("Weights"));
+                frame.render_widget(chart, left[1]);
+            }
+
+            let history_items: Vec<ListItem> = history.iter().rev().map(|roll| ListItem::new(roll.as_str())).collect();
+            let history_list = List::new(history_items)
+                .block(Block::default().borders(Borders::ALL).title("Roll History"));
+            frame.render_widget(history_list, columns[1]);
+        })?;
+
+        if event::poll(std::time::Duration::from_millis(200))? {
+            if let Event::Key(key) = event::read()? {
+                match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc => break,
+                    KeyCode::Char('r') | KeyCode::Enter => {
+                        history.push(source.pick(&mut rng).join(", "));
+                    }
+                    KeyCode::Down => {
+                        let next = list_state.selected().map_or(0, |i| (i + 1).min(names.len().saturating_sub(1)));
+                        list_state.select(Some(next));
+                    }
+                    KeyCode::Up => {
+                        let next = list_state.selected().map_or(0, |i| i.saturating_sub(1));
+                        list_state.select(Some(next));
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    restore_terminal(&mut terminal)
+}
@@ -0,0 +1,88 @@
+//! Watches the loot-table file for changes and re-parses it on the fly, so the
+//! GUI can pick up edits without the user having to quit and relaunch.
+
+use std::path::Path;
+use std::sync::mpsc::{channel, Receiver};
+use std::time::{Duration, Instant};
+
+use notify::{recommended_watcher, Event, EventKind, RecursiveMode, Watcher};
+
+use loot::LootSource;
+
+/// Minimum gap between two reloads, so that a single save (which can fire several
+/// write events in a row on some editors/filesystems) only triggers one re-parse.
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Result of re-parsing the watched file after a write event.
+pub(crate) enum Reload {
+    Ok(LootSource),
+    Err(String),
+}
+
+/// Spawns a background watcher on `path` and returns the receiving end of a
+/// channel that yields a [`Reload`] each time the file changes on disk and the
+/// debounce window has elapsed.
+pub(crate) fn watch(path: String) -> Receiver<Reload> {
+    let (tx, rx) = channel();
+
+    std::thread::spawn(move || {
+        let (notify_tx, notify_rx) = channel();
+        let mut watcher = match recommended_watcher(notify_tx) {
+            Ok(watcher) => watcher,
+            Err(err) => {
+                let _ = tx.send(Reload::Err(format!("Unable to start file watcher: {}", err)));
+                return;
+            }
+        };
+
+        // Watch the parent directory rather than the file itself. Editors that save
+        // via write-temp-then-rename (vim, many "safe write" modes, `sed -i`) replace
+        // the file's inode; a watch on the file directly dies silently the moment
+        // that happens, so the rename has to be observed from the directory instead.
+        let target = Path::new(&path);
+        let watch_dir = match target.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+
+        if let Err(err) = watcher.watch(watch_dir, RecursiveMode::NonRecursive) {
+            let _ = tx.send(Reload::Err(format!("Unable to watch {}: {}", path, err)));
+            return;
+        }
+
+        let mut last_reload = Instant::now() - DEBOUNCE;
+        for event in notify_rx {
+            let event: Event = match event {
+                Ok(event) => event,
+                Err(_) => continue,
+            };
+
+            // A rename-replace surfaces here as Remove/Create (or both) against the
+            // target path rather than a Modify, so all three have to be handled.
+            if !matches!(event.kind, EventKind::Modify(_) | EventKind::Create(_) | EventKind::Remove(_)) {
+                continue;
+            }
+
+            // The watch is on the parent directory only (non-recursive), and event
+            // paths come back resolved against it, so match on the file name.
+            if !event.paths.iter().any(|changed| changed.file_name() == target.file_name()) {
+                continue;
+            }
+
+            if last_reload.elapsed() < DEBOUNCE {
+                continue;
+            }
+            last_reload = Instant::now();
+
+            let reload = match loot::load_loot_table(&path) {
+                Ok(source) => Reload::Ok(source),
+                Err(err) => Reload::Err(err.to_string()),
+            };
+            if tx.send(reload).is_err() {
+                break; // the receiving end (the GUI) is gone, stop watching
+            }
+        }
+    });
+
+    rx
+}